@@ -0,0 +1,172 @@
+use crate::error::GitAiError;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+/// The real `git` binary, resolved once and cached for the lifetime of the
+/// process. git-ai installs itself as `git`, so a bare `PATH` lookup may well
+/// find git-ai instead of the git it is meant to wrap.
+static DISCOVERED_GIT: OnceLock<PathBuf> = OnceLock::new();
+
+/// Locates the real `git` binary, independent of whatever `git` happens to
+/// resolve to on the ambient `PATH`.
+///
+/// The first validated absolute path is cached, so repeated calls are cheap
+/// and every caller sees the same binary for the rest of the process.
+pub fn discover_git() -> Result<PathBuf, GitAiError> {
+    if let Some(path) = DISCOVERED_GIT.get() {
+        return Ok(path.clone());
+    }
+    let path = locate_git()?;
+    let _ = DISCOVERED_GIT.set(path);
+    Ok(DISCOVERED_GIT
+        .get()
+        .expect("git path was just set")
+        .clone())
+}
+
+/// Walks the candidate list and returns the first entry whose `--version`
+/// check succeeds.
+fn locate_git() -> Result<PathBuf, GitAiError> {
+    for candidate in candidate_paths() {
+        if let Some(git) = validate(&candidate) {
+            return Ok(git);
+        }
+    }
+    Err(GitAiError::Generic(
+        "could not locate a usable git binary".to_string(),
+    ))
+}
+
+/// Gathers candidate paths in priority order: the platform locate command
+/// first, then well-known install locations, then (on Windows) the registry.
+fn candidate_paths() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    candidates.extend(locate_command_results());
+    candidates.extend(well_known_locations());
+    #[cfg(windows)]
+    candidates.extend(registry_locations());
+    candidates
+}
+
+/// Runs the platform locate command (`which` on Unix, `where` on Windows) and
+/// returns every path it reports.
+fn locate_command_results() -> Vec<PathBuf> {
+    #[cfg(windows)]
+    let (cmd, arg) = ("where", "git");
+    #[cfg(not(windows))]
+    let (cmd, arg) = ("which", "git");
+
+    let output = match Command::new(cmd).arg(arg).output() {
+        Ok(output) if output.status.success() => output,
+        _ => return Vec::new(),
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Probes the locations a git install typically lands in when it is not on the
+/// shell's `PATH`.
+fn well_known_locations() -> Vec<PathBuf> {
+    #[cfg(windows)]
+    {
+        vec![
+            PathBuf::from(r"C:\Program Files\Git\cmd\git.exe"),
+            PathBuf::from(r"C:\Program Files\Git\bin\git.exe"),
+            PathBuf::from(r"C:\Program Files (x86)\Git\cmd\git.exe"),
+            PathBuf::from(r"C:\Program Files (x86)\Git\bin\git.exe"),
+        ]
+    }
+    #[cfg(not(windows))]
+    {
+        vec![
+            PathBuf::from("/usr/bin/git"),
+            PathBuf::from("/usr/local/bin/git"),
+            PathBuf::from("/opt/homebrew/bin/git"),
+            PathBuf::from("/opt/local/bin/git"),
+            PathBuf::from("/bin/git"),
+        ]
+    }
+}
+
+/// Reads the Git for Windows install path from
+/// `HKLM\SOFTWARE\GitForWindows\InstallPath` via `reg.exe`, keeping the crate
+/// free of a registry dependency in the same shell-out spirit as the rest of
+/// the git integration.
+#[cfg(windows)]
+fn registry_locations() -> Vec<PathBuf> {
+    let output = match Command::new("reg")
+        .args([
+            "query",
+            r"HKLM\SOFTWARE\GitForWindows",
+            "/v",
+            "InstallPath",
+        ])
+        .output()
+    {
+        Ok(output) if output.status.success() => output,
+        _ => return Vec::new(),
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            let value = line.split("REG_SZ").nth(1)?.trim();
+            if value.is_empty() {
+                None
+            } else {
+                Some(PathBuf::from(value))
+            }
+        })
+        .map(|install| vec![install.join(r"cmd\git.exe"), install.join(r"bin\git.exe")])
+        .unwrap_or_default()
+}
+
+/// Confirms a candidate is a working git by running `<candidate> --version`
+/// with its output discarded, returning its absolute path on success.
+///
+/// git-ai installs itself as `git`, so a `which git`/`where git` hit can be
+/// git-ai's own binary — which answers `--version` with exit 0 and would then
+/// be wrapped against itself. Any candidate that canonicalizes to the running
+/// executable is rejected before the version check so discovery only ever
+/// accepts the real git.
+fn validate(candidate: &Path) -> Option<PathBuf> {
+    // Prefer a canonical absolute path so later `--exec-path` resolution does
+    // not depend on the caller's working directory.
+    let canonical = candidate
+        .canonicalize()
+        .unwrap_or_else(|_| candidate.to_path_buf());
+
+    if is_running_exe(&canonical) {
+        return None;
+    }
+
+    let status = Command::new(candidate)
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .ok()?;
+
+    if !status.success() {
+        return None;
+    }
+
+    Some(canonical)
+}
+
+/// Whether `candidate` is git-ai's own executable. `candidate` may be either a
+/// canonical path or — when canonicalization failed — the raw candidate, so it
+/// is matched against both the raw and canonicalized forms of `current_exe` to
+/// avoid wrapping git-ai against itself on either comparison.
+fn is_running_exe(candidate: &Path) -> bool {
+    let Ok(exe) = std::env::current_exe() else {
+        return false;
+    };
+    candidate == exe || exe.canonicalize().map(|c| candidate == c).unwrap_or(false)
+}
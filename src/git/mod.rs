@@ -0,0 +1,3 @@
+pub mod binary;
+pub mod repo_discovery;
+pub mod repository;
@@ -0,0 +1,190 @@
+use crate::error::GitAiError;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A repository located by [`discover`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repository {
+    /// The directory that contains the `.git` entry.
+    pub work_dir: PathBuf,
+    /// The resolved git directory (the `.git` dir itself, or the target of a
+    /// `.git` file's `gitdir:` pointer for worktrees and submodules).
+    pub git_dir: PathBuf,
+}
+
+/// Knobs controlling how far [`discover_with`] is allowed to walk.
+#[derive(Debug, Clone)]
+pub struct DiscoverOptions {
+    /// Stop after this many parent hops, if set (cf. `git`'s discovery limit).
+    pub ceiling_height: Option<usize>,
+    /// Directories the walk may not cross, like `GIT_CEILING_DIRECTORIES`.
+    pub ceiling_dirs: Vec<PathBuf>,
+    /// When true, do not cross a filesystem boundary (compares `st_dev`).
+    pub stop_at_filesystem_boundary: bool,
+}
+
+impl Default for DiscoverOptions {
+    fn default() -> Self {
+        DiscoverOptions {
+            ceiling_height: None,
+            ceiling_dirs: Vec::new(),
+            stop_at_filesystem_boundary: true,
+        }
+    }
+}
+
+/// Why repository discovery stopped without a usable repository.
+#[derive(Debug)]
+pub enum DiscoverError {
+    /// Reached the filesystem root (or a ceiling) without finding a `.git`, or
+    /// found a `.git` file with no `gitdir:` line (`start` is then the
+    /// directory holding that file).
+    NoRepositoryFound { start: PathBuf },
+    /// Walked more than `ceiling_height` parent directories.
+    ExceededCeilingHeight { limit: usize },
+    /// Would have crossed a filesystem boundary with crossing disabled.
+    HitFilesystemBoundary { at: PathBuf },
+    /// Ceiling directories were configured but none prefixed the start path.
+    CeilingDirsNotPrefix { candidate: PathBuf },
+    /// An underlying filesystem error.
+    Io(io::Error),
+}
+
+impl fmt::Display for DiscoverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscoverError::NoRepositoryFound { start } => {
+                write!(f, "no git repository found starting from {}", start.display())
+            }
+            DiscoverError::ExceededCeilingHeight { limit } => {
+                write!(f, "exceeded discovery ceiling height of {limit} parent directories")
+            }
+            DiscoverError::HitFilesystemBoundary { at } => {
+                write!(f, "refusing to cross filesystem boundary at {}", at.display())
+            }
+            DiscoverError::CeilingDirsNotPrefix { candidate } => write!(
+                f,
+                "no ceiling directory is a prefix of {}",
+                candidate.display()
+            ),
+            DiscoverError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for DiscoverError {}
+
+impl From<io::Error> for DiscoverError {
+    fn from(err: io::Error) -> Self {
+        DiscoverError::Io(err)
+    }
+}
+
+impl From<DiscoverError> for GitAiError {
+    fn from(err: DiscoverError) -> Self {
+        GitAiError::Generic(err.to_string())
+    }
+}
+
+/// Discovers the repository containing `start` using default options.
+pub fn discover(start: &Path) -> Result<Repository, DiscoverError> {
+    discover_with(start, &DiscoverOptions::default())
+}
+
+/// Walks upward from `start` looking for a `.git` directory or file, honoring
+/// the ceiling height, ceiling directories, and filesystem-boundary limits in
+/// `opts`.
+pub fn discover_with(start: &Path, opts: &DiscoverOptions) -> Result<Repository, DiscoverError> {
+    let start = start.canonicalize()?;
+
+    if !opts.ceiling_dirs.is_empty() && !opts.ceiling_dirs.iter().any(|dir| start.starts_with(dir)) {
+        return Err(DiscoverError::CeilingDirsNotPrefix { candidate: start });
+    }
+
+    let mut current = start.clone();
+    let mut hops = 0usize;
+
+    loop {
+        if let Some(repo) = check_dir(&current)? {
+            return Ok(repo);
+        }
+
+        // A ceiling directory bounds the search: stop without crossing it.
+        if opts.ceiling_dirs.iter().any(|dir| dir == &current) {
+            return Err(DiscoverError::NoRepositoryFound { start });
+        }
+
+        let parent = match current.parent() {
+            Some(parent) => parent.to_path_buf(),
+            None => return Err(DiscoverError::NoRepositoryFound { start }),
+        };
+
+        if opts.stop_at_filesystem_boundary && crosses_boundary(&current, &parent)? {
+            return Err(DiscoverError::HitFilesystemBoundary { at: current });
+        }
+
+        hops += 1;
+        if let Some(limit) = opts.ceiling_height {
+            if hops > limit {
+                return Err(DiscoverError::ExceededCeilingHeight { limit });
+            }
+        }
+
+        current = parent;
+    }
+}
+
+/// Returns a [`Repository`] if `dir` holds a `.git` entry.
+fn check_dir(dir: &Path) -> Result<Option<Repository>, DiscoverError> {
+    let dot_git = dir.join(".git");
+    let meta = match std::fs::symlink_metadata(&dot_git) {
+        Ok(meta) => meta,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let git_dir = if meta.is_dir() {
+        dot_git
+    } else {
+        resolve_git_dir_file(dir, &dot_git)?
+    };
+
+    Ok(Some(Repository {
+        work_dir: dir.to_path_buf(),
+        git_dir,
+    }))
+}
+
+/// Resolves a `.git` file's `gitdir: <path>` pointer (worktrees, submodules).
+fn resolve_git_dir_file(work_dir: &Path, dot_git: &Path) -> Result<PathBuf, DiscoverError> {
+    let contents = std::fs::read_to_string(dot_git)?;
+    let pointer = contents
+        .lines()
+        .find_map(|line| line.strip_prefix("gitdir:"))
+        .map(str::trim)
+        .ok_or_else(|| DiscoverError::NoRepositoryFound {
+            start: work_dir.to_path_buf(),
+        })?;
+
+    let pointer = Path::new(pointer);
+    Ok(if pointer.is_absolute() {
+        pointer.to_path_buf()
+    } else {
+        work_dir.join(pointer)
+    })
+}
+
+#[cfg(unix)]
+fn crosses_boundary(a: &Path, b: &Path) -> Result<bool, DiscoverError> {
+    use std::os::unix::fs::MetadataExt;
+    let dev_a = std::fs::metadata(a)?.dev();
+    let dev_b = std::fs::metadata(b)?.dev();
+    Ok(dev_a != dev_b)
+}
+
+#[cfg(not(unix))]
+fn crosses_boundary(_a: &Path, _b: &Path) -> Result<bool, DiscoverError> {
+    // No portable `st_dev` equivalent; never treat a hop as crossing.
+    Ok(false)
+}
@@ -1,23 +1,59 @@
 use crate::error::GitAiError;
-use crate::git::repository::exec_git;
-use std::path::PathBuf;
+use crate::git::binary::discover_git;
+use crate::mdm::fs_util::replace_dir_symlink;
+use crate::mdm::install::resolve_install_base;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// How [`ensure_git_symlinks_mode`] should treat an existing libexec link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymlinkMode {
+    /// Always tear down and recreate the link.
+    Force,
+    /// Only rewrite the link when it is missing, dangling, or stale.
+    Verify,
+}
+
+/// What [`ensure_git_symlinks_mode`] did to the libexec link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymlinkOutcome {
+    /// The link already pointed at the right libexec; nothing changed.
+    Unchanged,
+    /// No link existed before, so a fresh one was created.
+    Created,
+    /// An existing entry was replaced: a dangling or stale link, something that
+    /// is not a symlink, or (in [`SymlinkMode::Force`]) any pre-existing link.
+    Repaired,
+}
 
 /// Ensures the libexec symlink exists for Fork compatibility.
 /// Creates a symlink from <binary_parent>/../libexec to the real git's libexec.
 pub fn ensure_git_symlinks() -> Result<(), GitAiError> {
-    // Get current executable path
-    let exe_path = std::env::current_exe()?;
+    // Verify is the default so repeated invocations leave an already-correct
+    // link untouched instead of churning the filesystem and racing concurrent
+    // git-ai processes on every call.
+    ensure_git_symlinks_mode(SymlinkMode::Verify).map(|_| ())
+}
 
-    // Get parent directories: binary_dir is e.g. ~/.git-ai/bin, base_dir is ~/.git-ai
-    let binary_dir = exe_path
-        .parent()
-        .ok_or_else(|| GitAiError::Generic("Cannot get binary directory".to_string()))?;
-    let base_dir = binary_dir
-        .parent()
-        .ok_or_else(|| GitAiError::Generic("Cannot get base directory".to_string()))?;
+/// Ensures the libexec symlink is in place, reporting what had to change.
+///
+/// In [`SymlinkMode::Verify`] the existing link target is compared (after
+/// canonicalization) against the freshly computed `--exec-path` parent and only
+/// rewritten when it is missing, dangling, or stale — e.g. after a git upgrade
+/// relocated `git-core`. The work is guarded by a lockfile under `base_dir` so
+/// concurrent git-ai invocations don't tear each other's link down mid-flight.
+pub fn ensure_git_symlinks_mode(mode: SymlinkMode) -> Result<SymlinkOutcome, GitAiError> {
+    // Resolve the install base (e.g. ~/.git-ai) in a way that survives
+    // content-addressable storage layouts and symlinked launchers.
+    let base_dir = resolve_install_base()?;
+    std::fs::create_dir_all(&base_dir)?;
 
-    // Get real git's exec-path (e.g. /usr/libexec/git-core)
-    let output = exec_git(&["--exec-path".to_string()])?;
+    // Get real git's exec-path (e.g. /usr/libexec/git-core). Resolve it against
+    // the discovered git binary rather than whatever `git` resolves to on PATH,
+    // since git-ai may itself be shadowing `git`.
+    let git = discover_git()?;
+    let output = Command::new(&git).arg("--exec-path").output()?;
     let exec_path = String::from_utf8(output.stdout)?.trim().to_string();
     let exec_path = PathBuf::from(exec_path);
 
@@ -26,19 +62,172 @@ pub fn ensure_git_symlinks() -> Result<(), GitAiError> {
         .parent()
         .ok_or_else(|| GitAiError::Generic("Cannot get libexec directory from exec-path".to_string()))?;
 
-    // Create symlink: base_dir/libexec -> /usr/libexec
     let symlink_path = base_dir.join("libexec");
 
-    // Remove existing symlink if present
-    if symlink_path.exists() || symlink_path.symlink_metadata().is_ok() {
-        std::fs::remove_file(&symlink_path)?;
+    // Hold the lock for the whole read-compare-rewrite so it stays atomic.
+    let _lock = LibexecLock::acquire(&base_dir)?;
+
+    match mode {
+        SymlinkMode::Force => {
+            // Report Created only when nothing was there before; an existing
+            // link (stale or not) that we tear down and replace is Repaired.
+            let pre_existing = symlink_path.symlink_metadata().is_ok();
+            replace_dir_symlink(libexec_target, &symlink_path)?;
+            Ok(if pre_existing {
+                SymlinkOutcome::Repaired
+            } else {
+                SymlinkOutcome::Created
+            })
+        }
+        SymlinkMode::Verify => verify_and_repair(libexec_target, &symlink_path),
     }
+}
+
+/// Rewrites the link only when it is absent, is something other than a
+/// symlink, is dangling, or resolves to a stale libexec.
+fn verify_and_repair(target: &Path, link: &Path) -> Result<SymlinkOutcome, GitAiError> {
+    match std::fs::read_link(link) {
+        Ok(current) if link_target_matches(&current, target, link) => Ok(SymlinkOutcome::Unchanged),
+        Ok(_) => {
+            // Present but dangling or stale.
+            replace_dir_symlink(target, link)?;
+            Ok(SymlinkOutcome::Repaired)
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            replace_dir_symlink(target, link)?;
+            Ok(SymlinkOutcome::Created)
+        }
+        Err(_) => {
+            // Something is there that is not a symlink (e.g. a real directory).
+            replace_dir_symlink(target, link)?;
+            Ok(SymlinkOutcome::Repaired)
+        }
+    }
+}
 
-    #[cfg(unix)]
-    std::os::unix::fs::symlink(libexec_target, &symlink_path)?;
+/// Whether the current link target resolves to the same directory as `desired`.
+/// A dangling or unresolvable target counts as a mismatch so it gets repaired.
+fn link_target_matches(current: &Path, desired: &Path, link: &Path) -> bool {
+    let resolved_current = if current.is_absolute() {
+        current.to_path_buf()
+    } else {
+        link.parent()
+            .map(|parent| parent.join(current))
+            .unwrap_or_else(|| current.to_path_buf())
+    };
 
+    matches!(
+        (resolved_current.canonicalize(), desired.canonicalize()),
+        (Ok(ref a), Ok(ref b)) if a == b
+    )
+}
+
+/// A best-effort advisory lock held for the lifetime of the guard, used to
+/// serialize libexec link changes across concurrent git-ai processes.
+struct LibexecLock {
+    path: PathBuf,
+}
+
+/// A lockfile whose owning process has vanished (killed or panicked between
+/// acquire and `Drop`) is reclaimed once it is older than this. Without this
+/// every later invocation would spin and then hard-error, turning a crashed
+/// run into a permanent failure of the git wrapper.
+const STALE_AFTER: std::time::Duration = std::time::Duration::from_secs(30);
+
+impl LibexecLock {
+    fn acquire(base_dir: &Path) -> Result<Self, GitAiError> {
+        let path = base_dir.join(".libexec.lock");
+        for _ in 0..50 {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    // Record the owner so a later run can tell a live holder
+                    // from an abandoned lockfile.
+                    use std::io::Write;
+                    let _ = writeln!(file, "{}", std::process::id());
+                    return Ok(LibexecLock { path });
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    if reclaim_if_stale(&path) {
+                        continue;
+                    }
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Err(GitAiError::Generic(format!(
+            "timed out acquiring libexec lock at {}",
+            path.display()
+        )))
+    }
+}
+
+/// Removes an existing lockfile when its owner is gone — either the process
+/// whose PID it names is no longer alive, or it is simply older than
+/// [`STALE_AFTER`]. Returns whether the lock was reclaimed so the caller can
+/// retry immediately instead of sleeping.
+fn reclaim_if_stale(path: &Path) -> bool {
+    let dead_owner = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| contents.trim().parse::<u32>().ok())
+        .map(|pid| !process_is_alive(pid))
+        .unwrap_or(false);
+
+    let expired = path
+        .metadata()
+        .and_then(|meta| meta.modified())
+        .and_then(|modified| modified.elapsed().map_err(|e| io::Error::other(e)))
+        .map(|age| age >= STALE_AFTER)
+        .unwrap_or(false);
+
+    if dead_owner || expired {
+        std::fs::remove_file(path).is_ok()
+    } else {
+        false
+    }
+}
+
+/// Best-effort liveness check for a lock owner, in the same shell-out spirit as
+/// the rest of the git integration: `kill -0` on Unix, `tasklist` on Windows.
+/// A check that cannot run conservatively reports the process as alive so the
+/// age-based fallback in [`reclaim_if_stale`] is what ultimately frees a lock.
+fn process_is_alive(pid: u32) -> bool {
     #[cfg(windows)]
-    std::os::windows::fs::symlink_dir(libexec_target, &symlink_path)?;
+    {
+        match Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {pid}"), "/NH"])
+            .output()
+        {
+            Ok(output) => String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()),
+            Err(_) => true,
+        }
+    }
+    #[cfg(not(windows))]
+    {
+        // `kill -0` also fails with EPERM when the process exists but belongs to
+        // another user (shared hosts), so a non-zero exit is not proof of death
+        // — only an explicit "no such process" is. Anything else is treated as
+        // alive, leaving age-based reclaim as the sole way to free the lock.
+        match Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stdout(std::process::Stdio::null())
+            .output()
+        {
+            Ok(output) if output.status.success() => true,
+            Ok(output) => !String::from_utf8_lossy(&output.stderr)
+                .to_lowercase()
+                .contains("no such process"),
+            Err(_) => true,
+        }
+    }
+}
 
-    Ok(())
+impl Drop for LibexecLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
 }
@@ -0,0 +1,188 @@
+use crate::error::GitAiError;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A filesystem operation on a libexec-style directory link failed. The
+/// attempted link path is always carried so callers can report what broke.
+#[derive(Debug)]
+pub enum FsError {
+    /// Creating the directory symlink (or junction) at `link` failed.
+    CreateLink { link: PathBuf, source: io::Error },
+    /// Removing the existing entry at `link` failed.
+    Remove { link: PathBuf, source: io::Error },
+}
+
+impl fmt::Display for FsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsError::CreateLink { link, source } => write!(
+                f,
+                "failed to create directory link at {}: {}",
+                link.display(),
+                source
+            ),
+            FsError::Remove { link, source } => write!(
+                f,
+                "failed to remove existing entry at {}: {}",
+                link.display(),
+                source
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FsError {}
+
+impl From<FsError> for GitAiError {
+    fn from(err: FsError) -> Self {
+        GitAiError::Generic(err.to_string())
+    }
+}
+
+/// Points a directory symlink at `link` towards `original`, removing whatever
+/// was there first.
+///
+/// On Unix this is a plain symlink. On Windows it attempts `symlink_dir`, and
+/// when that is refused for lack of Developer Mode / admin rights it falls back
+/// to a directory junction so non-elevated users still get Fork compatibility.
+pub fn replace_dir_symlink(original: &Path, link: &Path) -> Result<(), FsError> {
+    remove_symlink_or_dir(link)?;
+    create_dir_symlink(original, link)
+}
+
+#[cfg(unix)]
+fn create_dir_symlink(original: &Path, link: &Path) -> Result<(), FsError> {
+    std::os::unix::fs::symlink(original, link).map_err(|source| FsError::CreateLink {
+        link: link.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(windows)]
+fn create_dir_symlink(original: &Path, link: &Path) -> Result<(), FsError> {
+    match std::os::windows::fs::symlink_dir(original, link) {
+        Ok(()) => Ok(()),
+        Err(source) if source.kind() == io::ErrorKind::PermissionDenied => {
+            create_junction(original, link)
+        }
+        Err(source) => Err(FsError::CreateLink {
+            link: link.to_path_buf(),
+            source,
+        }),
+    }
+}
+
+/// Creates a directory junction via `mklink /J`, which (unlike a symlink) does
+/// not require elevated privileges.
+#[cfg(windows)]
+fn create_junction(original: &Path, link: &Path) -> Result<(), FsError> {
+    use std::process::{Command, Stdio};
+
+    let status = Command::new("cmd")
+        .args(["/C", "mklink", "/J"])
+        .arg(link)
+        .arg(original)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|source| FsError::CreateLink {
+            link: link.to_path_buf(),
+            source,
+        })?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(FsError::CreateLink {
+            link: link.to_path_buf(),
+            source: io::Error::new(io::ErrorKind::Other, "mklink /J failed"),
+        })
+    }
+}
+
+/// Removes whatever sits at `path`, choosing the right teardown for the entry:
+/// unlink a symlink, drop a junction without following it, or a guarded
+/// recursive removal of a real directory that clears read-only attributes
+/// first. A missing path is not an error.
+pub fn remove_symlink_or_dir(path: &Path) -> Result<(), FsError> {
+    let meta = match std::fs::symlink_metadata(path) {
+        Ok(meta) => meta,
+        Err(source) if source.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(source) => {
+            return Err(FsError::Remove {
+                link: path.to_path_buf(),
+                source,
+            })
+        }
+    };
+
+    let result = if is_link_meta(&meta) {
+        // Symlink or junction: remove the link itself, never its target.
+        remove_link(path)
+    } else if meta.file_type().is_dir() {
+        remove_dir_all_guarded(path)
+    } else {
+        std::fs::remove_file(path)
+    };
+
+    result.map_err(|source| FsError::Remove {
+        link: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Removes a symlink or junction without recursing into its target.
+fn remove_link(path: &Path) -> io::Result<()> {
+    #[cfg(windows)]
+    {
+        // A directory symlink or junction must be removed with `remove_dir`; a
+        // file symlink with `remove_file`. Try the file form, then the dir form.
+        std::fs::remove_file(path).or_else(|_| std::fs::remove_dir(path))
+    }
+    #[cfg(not(windows))]
+    {
+        std::fs::remove_file(path)
+    }
+}
+
+/// Recursively clears read-only attributes before removing the tree, mirroring
+/// the Windows pitfalls a hand-rolled `rm_rf` tends to trip over.
+fn remove_dir_all_guarded(path: &Path) -> io::Result<()> {
+    clear_readonly_recursive(path)?;
+    std::fs::remove_dir_all(path)
+}
+
+fn clear_readonly_recursive(path: &Path) -> io::Result<()> {
+    let meta = std::fs::symlink_metadata(path)?;
+
+    let mut perms = meta.permissions();
+    if perms.readonly() {
+        perms.set_readonly(false);
+        let _ = std::fs::set_permissions(path, perms);
+    }
+
+    // Never descend through a link while clearing attributes.
+    if meta.file_type().is_dir() && !is_link_meta(&meta) {
+        for entry in std::fs::read_dir(path)? {
+            clear_readonly_recursive(&entry?.path())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `meta` describes a symlink or a Windows reparse point (junction).
+fn is_link_meta(meta: &std::fs::Metadata) -> bool {
+    if meta.file_type().is_symlink() {
+        return true;
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+        return meta.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0;
+    }
+    #[cfg(not(windows))]
+    false
+}
@@ -0,0 +1,117 @@
+use crate::error::GitAiError;
+use std::path::{Path, PathBuf};
+
+/// Resolves the directory git-ai was installed into (the parent of its `bin`
+/// directory), robust to content-addressable storage layouts.
+///
+/// In a Nix store — and in some packaging that installs the binary behind a
+/// symlink — `current_exe()` resolves through the symlink into an immutable
+/// store path, so deriving the install base from it lands in a read-only or
+/// otherwise wrong directory. When the running binary is itself a symlink we
+/// follow rustc's lead and first try to imply the install dir from `argv[0]`,
+/// falling back to the canonicalized `current_exe()` only when that yields
+/// nothing usable.
+pub fn resolve_install_base() -> Result<PathBuf, GitAiError> {
+    let exe = std::env::current_exe()?;
+
+    let launched_via_symlink = std::fs::symlink_metadata(&exe)
+        .map(|meta| meta.file_type().is_symlink())
+        .unwrap_or(false);
+
+    if launched_via_symlink {
+        if let Ok(cwd) = std::env::current_dir() {
+            if let Some(argv0) = std::env::args_os().next() {
+                if let Some(base) = imply_base_from_argv0(Path::new(&argv0), &cwd) {
+                    return Ok(base);
+                }
+            }
+        }
+    }
+
+    // Not a symlink, or argv[0] told us nothing: use the resolved executable.
+    let resolved = exe.canonicalize().unwrap_or(exe);
+    install_base_from_binary(&resolved)
+}
+
+/// Implies the install base from `argv[0]` when it carries a path. A bare
+/// program name came from a `PATH` lookup and says nothing about where the
+/// binary lives, so it is rejected.
+///
+/// Only the *containing directory* is canonicalized — the final component is
+/// kept verbatim so we resolve the launcher's own location rather than
+/// following the symlink into the store.
+fn imply_base_from_argv0(argv0: &Path, cwd: &Path) -> Option<PathBuf> {
+    if argv0.components().count() <= 1 {
+        return None;
+    }
+
+    let absolute = if argv0.is_absolute() {
+        argv0.to_path_buf()
+    } else {
+        cwd.join(argv0)
+    };
+
+    let canonical_parent = absolute.parent()?.canonicalize().ok()?;
+    let binary = canonical_parent.join(absolute.file_name()?);
+    install_base_from_binary(&binary).ok()
+}
+
+/// Walks up from the binary path to the install base: `<base>/bin/<binary>`.
+fn install_base_from_binary(binary: &Path) -> Result<PathBuf, GitAiError> {
+    let binary_dir = binary
+        .parent()
+        .ok_or_else(|| GitAiError::Generic("Cannot get binary directory".to_string()))?;
+    let base_dir = binary_dir
+        .parent()
+        .ok_or_else(|| GitAiError::Generic("Cannot get base directory".to_string()))?;
+    Ok(base_dir.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gitai-install-{}-{}", std::process::id(), tag));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn plain_argv0_resolves_base() {
+        let root = scratch_dir("plain");
+        std::fs::create_dir_all(root.join("install/bin")).unwrap();
+        std::fs::write(root.join("install/bin/git-ai"), b"").unwrap();
+
+        let base = imply_base_from_argv0(Path::new("install/bin/git-ai"), &root).unwrap();
+        assert_eq!(base, root.join("install").canonicalize().unwrap());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn bare_name_yields_nothing() {
+        let root = scratch_dir("bare");
+        assert!(imply_base_from_argv0(Path::new("git-ai"), &root).is_none());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_launcher_uses_link_location_not_store() {
+        let root = scratch_dir("symlink");
+        std::fs::create_dir_all(root.join("store")).unwrap();
+        std::fs::create_dir_all(root.join("install/bin")).unwrap();
+        std::fs::write(root.join("store/git-ai"), b"").unwrap();
+        std::os::unix::fs::symlink(root.join("store/git-ai"), root.join("install/bin/git-ai"))
+            .unwrap();
+
+        // argv[0] points at the symlinked launcher; the install base must be the
+        // link's location, not the store the link resolves to.
+        let base = imply_base_from_argv0(Path::new("install/bin/git-ai"), &root).unwrap();
+        assert_eq!(base, root.join("install").canonicalize().unwrap());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
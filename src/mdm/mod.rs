@@ -0,0 +1,3 @@
+pub mod ensure_git_symlinks;
+pub mod fs_util;
+pub mod install;